@@ -0,0 +1,200 @@
+//! Export the currently loaded animation as an optimized GIF.
+//!
+//! Builds one shared 256-color palette across every frame with
+//! `imagequant` (median-cut/k-means style quantization), dithers each
+//! frame against it, and encodes with the `gif` crate directly so the
+//! shared palette and per-frame delays survive — the plain `image` crate's
+//! GIF encoder re-quantizes every frame independently and loses both.
+
+use std::fs::File;
+use std::path::Path;
+use std::time::Duration;
+
+use image::imageops::FilterType;
+use image::RgbaImage;
+
+use crate::frame::DecodedFrame;
+
+#[derive(Clone, Copy, Default)]
+pub struct ExportOptions {
+    /// Caps the output frame rate; extra frames are dropped and their
+    /// delay folded into the frame that's kept.
+    pub max_fps: Option<f64>,
+    /// Longest edge frames are downscaled to, preserving aspect ratio.
+    pub max_dimension: Option<u32>,
+}
+
+/// Quantizes `frames` against a shared palette and writes an animated GIF
+/// to `output_path`.
+pub fn export_gif(
+    frames: &[DecodedFrame],
+    output_path: &Path,
+    options: &ExportOptions,
+) -> Result<(), String> {
+    let frames = downsample_frame_rate(frames, options.max_fps);
+    if frames.is_empty() {
+        return Err("no frames to export".to_string());
+    }
+
+    let scaled: Vec<(RgbaImage, Duration)> = frames
+        .into_iter()
+        .map(|(image, delay)| (clamp_dimensions(image, options.max_dimension), delay))
+        .collect();
+    let (width, height) = {
+        let (first, _) = &scaled[0];
+        (first.width(), first.height())
+    };
+
+    let mut liq = imagequant::new();
+    liq.set_speed(5).map_err(|err| err.to_string())?;
+    liq.set_quality(0, 100).map_err(|err| err.to_string())?;
+
+    let mut liq_images = Vec::with_capacity(scaled.len());
+    for (rgba, _) in &scaled {
+        let pixels = to_liq_pixels(rgba);
+        let img = liq
+            .new_image(pixels, rgba.width() as usize, rgba.height() as usize, 0.0)
+            .map_err(|err| err.to_string())?;
+        liq_images.push(img);
+    }
+
+    // Build one histogram/palette from every frame instead of quantizing
+    // each frame in isolation, so colors stay consistent across the
+    // animation rather than flickering between per-frame palettes.
+    let mut histogram = imagequant::Histogram::new(&liq);
+    for img in &mut liq_images {
+        histogram.add_image(&liq, img).map_err(|err| err.to_string())?;
+    }
+    let mut quantized = histogram.quantize(&liq).map_err(|err| err.to_string())?;
+    quantized.set_dithering_level(1.0).map_err(|err| err.to_string())?;
+
+    let palette = quantized.palette().to_vec();
+    let mut global_palette = Vec::with_capacity(palette.len() * 3);
+    for color in &palette {
+        global_palette.extend_from_slice(&[color.r, color.g, color.b]);
+    }
+
+    let file = File::create(output_path).map_err(|err| err.to_string())?;
+    let mut encoder = gif::Encoder::new(file, width as u16, height as u16, &global_palette)
+        .map_err(|err| err.to_string())?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(|err| err.to_string())?;
+
+    for (img, (_, delay)) in liq_images.iter_mut().zip(scaled.iter()) {
+        let (indices, _) = quantized.remapped(img).map_err(|err| err.to_string())?;
+        let mut gif_frame =
+            gif::Frame::from_indexed_pixels(width as u16, height as u16, indices, None);
+        gif_frame.delay = (delay.as_millis() / 10).max(1) as u16;
+        encoder.write_frame(&gif_frame).map_err(|err| err.to_string())?;
+    }
+
+    Ok(())
+}
+
+fn to_liq_pixels(image: &RgbaImage) -> Vec<imagequant::RGBA> {
+    image
+        .pixels()
+        .map(|p| imagequant::RGBA::new(p[0], p[1], p[2], p[3]))
+        .collect()
+}
+
+fn clamp_dimensions(image: RgbaImage, max_dimension: Option<u32>) -> RgbaImage {
+    let Some(max_dimension) = max_dimension else {
+        return image;
+    };
+    let longest = image.width().max(image.height());
+    if longest <= max_dimension {
+        return image;
+    }
+
+    let scale = max_dimension as f32 / longest as f32;
+    let width = (image.width() as f32 * scale).round().max(1.0) as u32;
+    let height = (image.height() as f32 * scale).round().max(1.0) as u32;
+    image::imageops::resize(&image, width, height, FilterType::Lanczos3)
+}
+
+/// Drops frames so the output doesn't exceed `max_fps`, folding each
+/// dropped frame's delay into the frame that's kept so total playback
+/// time is preserved.
+fn downsample_frame_rate(
+    frames: &[DecodedFrame],
+    max_fps: Option<f64>,
+) -> Vec<(RgbaImage, Duration)> {
+    let as_rgba: Vec<(RgbaImage, Duration)> = frames
+        .iter()
+        .filter_map(|frame| {
+            let raw: Vec<u8> = frame
+                .image
+                .pixels
+                .iter()
+                .flat_map(|color| color.to_array())
+                .collect();
+            RgbaImage::from_raw(frame.image.width() as u32, frame.image.height() as u32, raw)
+                .map(|img| (img, frame.delay))
+        })
+        .collect();
+
+    let Some(max_fps) = max_fps.filter(|fps| *fps > 0.0) else {
+        return as_rgba;
+    };
+    let min_duration = Duration::from_secs_f64(1.0 / max_fps);
+
+    let mut kept: Vec<(RgbaImage, Duration)> = Vec::new();
+    let mut carry = Duration::ZERO;
+    for (image, delay) in as_rgba {
+        match kept.last_mut() {
+            Some((_, last_delay)) if carry + delay < min_duration => {
+                *last_delay += delay;
+                carry += delay;
+            }
+            _ => {
+                kept.push((image, delay));
+                carry = Duration::ZERO;
+            }
+        }
+    }
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui::ColorImage;
+
+    fn frame(delay_ms: u64) -> DecodedFrame {
+        let image = ColorImage::from_rgba_unmultiplied([1, 1], &[255, 0, 0, 255]);
+        DecodedFrame {
+            image,
+            delay: Duration::from_millis(delay_ms),
+        }
+    }
+
+    #[test]
+    fn no_max_fps_keeps_every_frame() {
+        let frames = vec![frame(10), frame(10), frame(10)];
+        let out = downsample_frame_rate(&frames, None);
+        assert_eq!(out.len(), 3);
+    }
+
+    #[test]
+    fn drops_frames_faster_than_max_fps_and_folds_their_delay() {
+        // max_fps of 10 means frames closer together than 100ms are folded
+        // into the previous kept frame instead of kept separately.
+        let frames = vec![frame(30), frame(30), frame(30), frame(30)];
+        let out = downsample_frame_rate(&frames, Some(10.0));
+
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].1, Duration::from_millis(120));
+    }
+
+    #[test]
+    fn keeps_frames_already_slower_than_max_fps() {
+        let frames = vec![frame(200), frame(200), frame(200)];
+        let out = downsample_frame_rate(&frames, Some(10.0));
+        assert_eq!(out.len(), 3);
+        for (_, delay) in &out {
+            assert_eq!(*delay, Duration::from_millis(200));
+        }
+    }
+}
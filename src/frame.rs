@@ -0,0 +1,58 @@
+//! Shared types for the animated-frame pipeline that both `gif` and
+//! `video` decode into, so the UI's playback loop doesn't need to know
+//! which backend produced the frames it's drawing.
+
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread;
+use std::time::Duration;
+
+use eframe::egui::ColorImage;
+
+pub struct DecodedFrame {
+    pub image: ColorImage,
+    pub delay: Duration,
+}
+
+/// Capacity of a decode channel; bounds how far a worker thread can race
+/// ahead of playback before blocking, so decoding self-throttles to
+/// roughly playback speed instead of racing to decode everything up front.
+const DECODE_CHANNEL_CAPACITY: usize = 4;
+
+/// Message a background decoder thread sends to the UI-side player.
+pub enum DecodeMsg {
+    Frame(DecodedFrame),
+    Done,
+    Error(String),
+}
+
+/// Spawns `decode` on a worker thread wired to a bounded channel, and
+/// returns the receiving end. Shared by the GIF and video backends so
+/// the channel plumbing isn't duplicated per decoder.
+pub fn spawn_decode_thread<F>(decode: F) -> Receiver<DecodeMsg>
+where
+    F: FnOnce(SyncSender<DecodeMsg>) + Send + 'static,
+{
+    let (tx, rx) = mpsc::sync_channel(DECODE_CHANNEL_CAPACITY);
+    thread::spawn(move || decode(tx));
+    rx
+}
+
+/// Common interface the animation loop in `main` drives, regardless of
+/// whether the frames come from a GIF or a video.
+pub trait FramePlayer {
+    fn current_frame(&self) -> Option<&DecodedFrame>;
+    fn advance(&mut self) -> std::io::Result<()>;
+
+    /// Rewinds to the first frame. Only videos support this meaningfully
+    /// today; GIFs just replay their scratch cache from the top.
+    fn seek_to_start(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    /// Collects every frame of the animation for export. GIFs satisfy this
+    /// from their scratch cache and videos by decoding the file again; the
+    /// default implementation returns an error.
+    fn export_frames(&mut self) -> Result<Vec<DecodedFrame>, String> {
+        Err("this source doesn't support export yet".to_string())
+    }
+}
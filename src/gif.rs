@@ -0,0 +1,376 @@
+//! Background GIF decoding with a scratch-file frame cache.
+//!
+//! Decoding a multi-hundred-frame GIF synchronously on the UI thread stalls
+//! the window, and keeping every decoded frame resident in memory scales
+//! badly with animation length. `GifPlayer` decodes on a worker thread,
+//! streaming frames to the UI over a bounded channel so decoding
+//! self-throttles to playback speed, and mirrors each frame to a scratch
+//! file on disk as it goes. Once the first loop finishes, subsequent loops
+//! replay the scratch file instead of re-invoking the GIF decoder, and only
+//! a handful of frames are ever held in memory at once.
+
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, SyncSender};
+use std::time::Duration;
+
+use eframe::egui::ColorImage;
+use image::codecs::gif::GifDecoder;
+use image::AnimationDecoder;
+
+use crate::frame::{self, DecodeMsg, DecodedFrame, FramePlayer};
+
+/// Decoded frames kept resident at once (triple-buffered) while looping.
+const RESIDENT_FRAMES: usize = 3;
+
+/// Distinguishes scratch files belonging to different `GifPlayer`s in the
+/// same process. Docking support (chunk0-4) lets several GIF tabs stay
+/// open at once, so the process id alone is no longer a unique key.
+static NEXT_INSTANCE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Scratch-file path for a single `GifPlayer` instance.
+fn instance_scratch_path() -> PathBuf {
+    let id = NEXT_INSTANCE_ID.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!("timba-gif-{}-{}.raw", std::process::id(), id))
+}
+
+/// Removes every scratch file left behind by this process's `GifPlayer`s.
+/// Called alongside the socket cleanup on exit. Each player also removes
+/// its own file on `Drop`, so this is mainly a backstop for the abrupt
+/// `Ctrl-C` exit path.
+pub fn cleanup_scratch_files() {
+    let Ok(entries) = std::env::temp_dir().read_dir() else {
+        return;
+    };
+    let prefix = format!("timba-gif-{}-", std::process::id());
+    for entry in entries.flatten() {
+        if entry.file_name().to_string_lossy().starts_with(&prefix) {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+}
+
+/// Streams decoded GIF frames to the UI thread and caches them on disk so
+/// later loops don't have to re-decode.
+pub struct GifPlayer {
+    scratch_path: PathBuf,
+    receiver: Option<Receiver<DecodeMsg>>,
+    scratch_reader: Option<BufReader<File>>,
+    /// Set once the worker thread signals it has written every frame.
+    total_frames: Option<usize>,
+    /// Frames received from the decoder so far, including ones already
+    /// evicted from `resident`.
+    decoded_count: usize,
+    resident: VecDeque<DecodedFrame>,
+    /// Index of the first frame in `resident`.
+    resident_start: usize,
+    current: usize,
+}
+
+impl GifPlayer {
+    /// Opens `path`, spawns the background decoder, and returns a player
+    /// positioned at frame 0 once at least one frame has arrived.
+    pub fn spawn(path: &Path) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        let scratch_path = instance_scratch_path();
+        let scratch_write = BufWriter::new(File::create(&scratch_path)?);
+
+        let rx = frame::spawn_decode_thread(move |tx| decode_loop(file, scratch_write, tx));
+
+        let mut player = Self {
+            scratch_path,
+            receiver: Some(rx),
+            scratch_reader: None,
+            total_frames: None,
+            decoded_count: 0,
+            resident: VecDeque::new(),
+            resident_start: 0,
+            current: 0,
+        };
+        // Block for the first frame so callers have something to show
+        // immediately; everything after this is streamed lazily.
+        player.fill_to(0)?;
+        Ok(player)
+    }
+
+    pub fn frame_count(&self) -> Option<usize> {
+        self.total_frames
+    }
+
+    /// Blocks until the worker thread has decoded and cached every frame,
+    /// then reads them all back from the scratch file. Used by export,
+    /// which needs the whole animation rather than a few resident frames.
+    pub fn collect_all_frames(&mut self) -> std::io::Result<Vec<DecodedFrame>> {
+        while self.total_frames.is_none() {
+            if self.poll_decoder(self.decoded_count)?.is_none() && self.total_frames.is_none() {
+                // Receiver disconnected without a `Done`; treat what we have
+                // as the whole animation.
+                self.total_frames = Some(self.decoded_count);
+            }
+        }
+
+        let total = self.total_frames.unwrap_or(0);
+        let mut frames = Vec::with_capacity(total);
+        for index in 0..total {
+            frames.push(self.read_scratch_frame(index)?);
+        }
+        Ok(frames)
+    }
+
+    /// Ensures `index` is resident, fetching from the live decoder or the
+    /// scratch file and evicting the oldest frame to stay within
+    /// `RESIDENT_FRAMES`.
+    fn fill_to(&mut self, index: usize) -> std::io::Result<()> {
+        while index >= self.resident_start + self.resident.len() {
+            let frame = self.next_frame()?;
+            self.resident.push_back(frame);
+            if self.resident.len() > RESIDENT_FRAMES {
+                self.resident.pop_front();
+                self.resident_start += 1;
+            }
+        }
+        // Looping back to an earlier frame after the cache has moved past
+        // it: the scratch file is the source of truth, so rewind and
+        // re-read from there.
+        if index < self.resident_start {
+            self.resident.clear();
+            self.resident_start = index;
+            let frame = self.read_scratch_frame(index)?;
+            self.resident.push_back(frame);
+        }
+        Ok(())
+    }
+
+    fn next_frame(&mut self) -> std::io::Result<DecodedFrame> {
+        let next_index = self.resident_start + self.resident.len();
+        // Only the decoder's own next frame is safe to pull from the live
+        // channel. A seek back to an earlier frame (e.g. the Home key
+        // during the first loop, before `total_frames` is known) can leave
+        // `next_index` behind `decoded_count`; in that case the frame was
+        // already decoded and cached earlier, so it must come from the
+        // scratch file instead of draining the channel out of order.
+        if self.total_frames.is_none() && next_index == self.decoded_count {
+            if let Some(frame) = self.poll_decoder(next_index)? {
+                return Ok(frame);
+            }
+            // `poll_decoder` just consumed `Done`: `next_index` was one
+            // past the last real frame (the loop wrapping around for the
+            // first time), so wrap it to frame 0 instead of reading past
+            // the end of the now-complete scratch file.
+            if let Some(total) = self.total_frames {
+                if total > 0 {
+                    return self.read_scratch_frame(next_index % total);
+                }
+            }
+        }
+        self.read_scratch_frame(next_index)
+    }
+
+    /// Drains the decode channel for `index`'s frame. Blocks only while the
+    /// worker hasn't produced it yet (i.e. we're still ahead of the cache).
+    fn poll_decoder(&mut self, index: usize) -> std::io::Result<Option<DecodedFrame>> {
+        let Some(receiver) = self.receiver.as_ref() else {
+            return Ok(None);
+        };
+        loop {
+            match receiver.recv() {
+                Ok(DecodeMsg::Frame(frame)) => {
+                    self.decoded_count += 1;
+                    return Ok(Some(frame));
+                }
+                Ok(DecodeMsg::Done) => {
+                    self.total_frames = Some(index);
+                    self.receiver = None;
+                    return Ok(None);
+                }
+                Ok(DecodeMsg::Error(err)) => {
+                    self.receiver = None;
+                    return Err(std::io::Error::new(std::io::ErrorKind::Other, err));
+                }
+                Err(_) => {
+                    self.receiver = None;
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    fn read_scratch_frame(&mut self, index: usize) -> std::io::Result<DecodedFrame> {
+        if self.scratch_reader.is_none() {
+            self.scratch_reader = Some(BufReader::new(File::open(&self.scratch_path)?));
+        }
+        let reader = self.scratch_reader.as_mut().unwrap();
+        reader.seek(SeekFrom::Start(0))?;
+        for _ in 0..index {
+            skip_frame(reader)?;
+        }
+        read_frame(reader)
+    }
+}
+
+impl Drop for GifPlayer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.scratch_path);
+    }
+}
+
+impl FramePlayer for GifPlayer {
+    fn current_frame(&self) -> Option<&DecodedFrame> {
+        self.resident.get(self.current - self.resident_start)
+    }
+
+    /// Advances to the next frame (wrapping once the total frame count is
+    /// known), decoding or reading from the scratch cache as needed.
+    fn advance(&mut self) -> std::io::Result<()> {
+        let next = match self.total_frames {
+            Some(total) if total > 0 => (self.current + 1) % total,
+            _ => self.current + 1,
+        };
+        self.fill_to(next)?;
+        self.current = next;
+        Ok(())
+    }
+
+    fn seek_to_start(&mut self) -> std::io::Result<()> {
+        self.fill_to(0)?;
+        self.current = 0;
+        Ok(())
+    }
+
+    fn export_frames(&mut self) -> Result<Vec<DecodedFrame>, String> {
+        self.collect_all_frames()
+            .map_err(|err| format!("failed to collect frames for export: {}", err))
+    }
+}
+
+/// Runs on the worker thread: decodes frames one at a time, forwarding each
+/// over the bounded channel and appending it to the scratch file. The
+/// bounded channel means decoding naturally pauses once the UI is a few
+/// frames ahead, instead of racing to decode everything up front.
+fn decode_loop(file: File, mut scratch: BufWriter<File>, tx: SyncSender<DecodeMsg>) {
+    let decoder = match GifDecoder::new(file) {
+        Ok(decoder) => decoder,
+        Err(err) => {
+            let _ = tx.send(DecodeMsg::Error(format!("failed to open GIF: {}", err)));
+            return;
+        }
+    };
+
+    for frame_result in decoder.into_frames() {
+        let frame = match frame_result {
+            Ok(frame) => frame,
+            Err(err) => {
+                let _ = tx.send(DecodeMsg::Error(format!("failed to decode frame: {}", err)));
+                return;
+            }
+        };
+
+        let delay = Duration::from(frame.delay());
+        let buffer = frame.into_buffer();
+        let (width, height) = buffer.dimensions();
+        let raw = buffer.into_raw();
+
+        if let Err(err) = write_frame(&mut scratch, width, height, delay, &raw) {
+            let _ = tx.send(DecodeMsg::Error(format!("failed to write scratch frame: {}", err)));
+            return;
+        }
+        // Flushed per frame, not just at the end: a seek can make the UI
+        // thread read this frame back from the scratch file (via a
+        // separate file handle) before decoding finishes, and `BufWriter`
+        // only hands bytes to the OS once its internal buffer fills.
+        if let Err(err) = scratch.flush() {
+            let _ = tx.send(DecodeMsg::Error(format!("failed to flush scratch frame: {}", err)));
+            return;
+        }
+
+        let image = ColorImage::from_rgba_unmultiplied([width as usize, height as usize], &raw);
+        if tx.send(DecodeMsg::Frame(DecodedFrame { image, delay })).is_err() {
+            // UI side dropped the player; nothing left to do.
+            return;
+        }
+    }
+
+    let _ = scratch.flush();
+    let _ = tx.send(DecodeMsg::Done);
+}
+
+/// Scratch file layout, repeated per frame: width:u32, height:u32,
+/// delay_millis:u64, followed by `width * height * 4` raw RGBA bytes.
+fn write_frame(
+    out: &mut impl Write,
+    width: u32,
+    height: u32,
+    delay: Duration,
+    rgba: &[u8],
+) -> std::io::Result<()> {
+    out.write_all(&width.to_le_bytes())?;
+    out.write_all(&height.to_le_bytes())?;
+    out.write_all(&(delay.as_millis() as u64).to_le_bytes())?;
+    out.write_all(rgba)
+}
+
+fn read_frame(input: &mut impl Read) -> std::io::Result<DecodedFrame> {
+    let (width, height, delay) = read_frame_header(input)?;
+    let mut rgba = vec![0u8; (width * height * 4) as usize];
+    input.read_exact(&mut rgba)?;
+    let image = ColorImage::from_rgba_unmultiplied([width as usize, height as usize], &rgba);
+    Ok(DecodedFrame { image, delay })
+}
+
+fn skip_frame(input: &mut (impl Read + Seek)) -> std::io::Result<()> {
+    let (width, height, _) = read_frame_header(input)?;
+    input.seek(SeekFrom::Current((width * height * 4) as i64))?;
+    Ok(())
+}
+
+fn read_frame_header(input: &mut impl Read) -> std::io::Result<(u32, u32, Duration)> {
+    let mut width_bytes = [0u8; 4];
+    let mut height_bytes = [0u8; 4];
+    let mut delay_bytes = [0u8; 8];
+    input.read_exact(&mut width_bytes)?;
+    input.read_exact(&mut height_bytes)?;
+    input.read_exact(&mut delay_bytes)?;
+    Ok((
+        u32::from_le_bytes(width_bytes),
+        u32::from_le_bytes(height_bytes),
+        Duration::from_millis(u64::from_le_bytes(delay_bytes)),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn rgba(width: u32, height: u32, value: u8) -> Vec<u8> {
+        vec![value; (width * height * 4) as usize]
+    }
+
+    #[test]
+    fn write_then_read_roundtrips_a_frame() {
+        let mut buf = Vec::new();
+        let raw = rgba(2, 3, 42);
+        write_frame(&mut buf, 2, 3, Duration::from_millis(100), &raw).unwrap();
+
+        let mut cursor = Cursor::new(buf);
+        let frame = read_frame(&mut cursor).unwrap();
+        assert_eq!(frame.image.width(), 2);
+        assert_eq!(frame.image.height(), 3);
+        assert_eq!(frame.delay, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn skip_frame_advances_past_a_frame_without_reading_it() {
+        let mut buf = Vec::new();
+        write_frame(&mut buf, 2, 2, Duration::from_millis(50), &rgba(2, 2, 1)).unwrap();
+        write_frame(&mut buf, 2, 2, Duration::from_millis(75), &rgba(2, 2, 2)).unwrap();
+
+        let mut cursor = Cursor::new(buf);
+        skip_frame(&mut cursor).unwrap();
+        let second = read_frame(&mut cursor).unwrap();
+        assert_eq!(second.delay, Duration::from_millis(75));
+    }
+}
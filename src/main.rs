@@ -1,28 +1,62 @@
+mod export;
+mod frame;
+mod gif;
+mod protocol;
+mod toast;
+mod video;
+mod view;
+
 use std::env;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::os::unix::net::{UnixStream, UnixListener};
-use std::io::{Read, Write};
+use std::io::Write;
 use std::thread;
 use std::sync::mpsc;
 use std::fs;
 use eframe::{egui, App, Frame};
 use image::io::Reader as ImageReader;
-use image::codecs::gif::GifDecoder;
-use image::AnimationDecoder;
 use image::GenericImageView;
 
+use export::ExportOptions;
+use protocol::{Command, Reply};
+use toast::{Message, ToastHub};
+use view::ImageView;
+
 const SOCKET_PATH: &str = "/tmp/timba.sock";
 
+/// A parsed `ExportGif` command from the control socket.
+struct ExportRequest {
+    output_path: PathBuf,
+    options: ExportOptions,
+}
+
 struct TimbaApp {
-    texture: Option<egui::TextureHandle>,
-    image_path: String,
-    error_message: Option<String>,
-    original_size: Option<egui::Vec2>,
+    tree: egui_dock::Tree<ImageView>,
+    toasts: ToastHub,
     image_receiver: mpsc::Receiver<String>,
-    gif_frames: Option<Vec<(egui::ColorImage, std::time::Duration)>>,
-    current_frame: usize,
-    last_frame_time: std::time::Instant,
-    is_animated: bool,
+    export_receiver: mpsc::Receiver<ExportRequest>,
+    focus_receiver: mpsc::Receiver<()>,
+}
+
+/// Bridges `egui_dock`'s per-tab callbacks to `ImageView` and lets tabs
+/// report failures as toasts instead of replacing their content.
+struct ImageTabViewer<'a> {
+    toasts: &'a mut ToastHub,
+}
+
+impl egui_dock::TabViewer for ImageTabViewer<'_> {
+    type Tab = ImageView;
+
+    fn title(&mut self, tab: &mut ImageView) -> egui::WidgetText {
+        tab.title().into()
+    }
+
+    fn ui(&mut self, ui: &mut egui::Ui, tab: &mut ImageView) {
+        if let Err(err) = tab.tick(ui.ctx()) {
+            self.toasts.push(Message::Error(err));
+        }
+        tab.show(ui);
+    }
 }
 
 impl App for TimbaApp {
@@ -30,208 +64,65 @@ impl App for TimbaApp {
         // Always request repaint to keep checking for new messages
         ctx.request_repaint();
 
-        // Check for new image path requests
-        if let Ok(new_path) = self.image_receiver.try_recv() {
-            println!(">>> Received new image path in UI thread: {}", new_path);
-            println!(">>> Previous path was: {}", self.image_path);
-            self.image_path = new_path;
-            self.texture = None;
-            self.error_message = None;
-            self.original_size = None;
-            // Reset animation state when loading new image
-            self.gif_frames = None;
-            self.current_frame = 0;
-            self.is_animated = false;
-
-            // Load the image immediately
-            self.load_image(ctx);
-
-            println!(">>> Image loaded and UI updated");
+        self.toasts.update();
+
+        // Check for new image path requests and open each as its own tab
+        // rather than replacing whatever is already open.
+        while let Ok(new_path) = self.image_receiver.try_recv() {
+            self.tree.push_to_focused_leaf(ImageView::new(new_path));
         }
 
-        // Remove the redundant loading logic - only load on startup if no image is set
-        if self.texture.is_none() && !self.image_path.is_empty() && self.error_message.is_none() {
-            // This should only happen on initial startup
-            self.load_image(ctx);
+        while let Ok(request) = self.export_receiver.try_recv() {
+            self.export_focused_tab(request);
         }
 
-        // Handle GIF animation timing
-        if self.is_animated {
-            if let Some(ref frames) = self.gif_frames {
-                let current_time = std::time::Instant::now();
-                if self.current_frame < frames.len() {
-                    let frame_duration = frames[self.current_frame].1;
-
-                    if current_time.duration_since(self.last_frame_time) >= frame_duration {
-                        self.current_frame = (self.current_frame + 1) % frames.len();
-                        self.last_frame_time = current_time;
-                        self.update_texture(ctx);
-                    }
-                }
-            }
+        while self.focus_receiver.try_recv().is_ok() {
+            // Raising the actual OS window isn't exposed by this eframe
+            // version; surface the request so it's at least visible.
+            self.toasts.push(Message::Info("Focus requested over socket".to_string()));
         }
 
-        // Rest of the update function remains the same
-        egui::CentralPanel::default().show(ctx, |ui| {
-            // Show error message if any
-            if let Some(error) = &self.error_message {
-                ui.label(format!("Error: {}", error));
-                return;
-            }
+        let mut viewer = ImageTabViewer {
+            toasts: &mut self.toasts,
+        };
+        egui_dock::DockArea::new(&mut self.tree).show(ctx, &mut viewer);
 
-            // Show the image with proper scaling
-            if let Some(texture) = &self.texture {
-                if let Some(original_size) = self.original_size {
-                    // Get available space in the panel
-                    let available_size = ui.available_size();
-
-                    // Calculate scale factor to fit the image in the available space
-                    let scale_x = available_size.x / original_size.x;
-                    let scale_y = available_size.y / original_size.y;
-                    let scale = scale_x.min(scale_y).min(1.0); // Don't scale above 100%
-
-                    // Calculate displayed size
-                    let displayed_size = egui::vec2(
-                        original_size.x * scale,
-                        original_size.y * scale
-                    );
-
-                    // Center the image
-                    let padding_x = (available_size.x - displayed_size.x) / 2.0;
-                    let padding_y = (available_size.y - displayed_size.y) / 2.0;
-
-                    ui.allocate_space(egui::vec2(available_size.x, padding_y));
-
-                    ui.horizontal(|ui| {
-                        ui.add_space(padding_x);
-                        ui.add(egui::Image::new(texture, displayed_size));
-                    });
-                }
-            } else {
-                ui.label("Loading image...");
-            }
-        });
+        self.toasts.show(ctx);
     }
 }
 
 impl TimbaApp {
-    fn new(image_path: String, image_receiver: mpsc::Receiver<String>) -> Self {
+    fn new(
+        image_path: String,
+        image_receiver: mpsc::Receiver<String>,
+        export_receiver: mpsc::Receiver<ExportRequest>,
+        focus_receiver: mpsc::Receiver<()>,
+        toasts: ToastHub,
+    ) -> Self {
         Self {
-            texture: None,
-            image_path,
-            error_message: None,
-            original_size: None,
+            tree: egui_dock::Tree::new(vec![ImageView::new(image_path)]),
+            toasts,
             image_receiver,
-            gif_frames: None,
-            current_frame: 0,
-            last_frame_time: std::time::Instant::now(),
-            is_animated: false,
-        }
-    }
-
-    fn load_image(&mut self, ctx: &egui::Context) {
-        let path = Path::new(&self.image_path);
-
-        // Check if it's a GIF
-        if path.extension().and_then(|s| s.to_str()) == Some("gif") {
-            self.load_gif(ctx);
-        } else {
-            self.load_static_image(ctx);
-        }
-    }
-
-    // The load_image function
-    fn load_static_image(&mut self, ctx: &egui::Context) {
-        let path = Path::new(&self.image_path);
-
-        // Try to load the image
-        match image::open(path) {
-            Ok(img) => {
-                let width = img.width() as f32;
-                let height = img.height() as f32;
-                let size = [img.width() as _, img.height() as _];
-                let image_buffer = img.to_rgba8();
-                let pixels = image_buffer.into_vec();
-
-                // Store original dimensions
-                self.original_size = Some(egui::vec2(width, height));
-
-                // Create texture
-                let texture = ctx.load_texture(
-                    path.file_name().unwrap().to_string_lossy(),
-                    egui::ColorImage::from_rgba_unmultiplied(size, &pixels),
-                    egui::TextureFilter::Linear,
-                );
-
-                self.texture = Some(texture);
-                // Ensure static images don't animate
-                self.is_animated = false;
-                self.gif_frames = None;
-                println!(">>> Static image loaded successfully: {}x{}", width, height);
-            }
-            Err(err) => {
-                self.error_message = Some(format!("Failed to load image: {}", err));
-                println!(">>> Failed to load image: {}", err);
-            }
+            export_receiver,
+            focus_receiver,
         }
     }
 
-    fn load_gif(&mut self, ctx: &egui::Context) {
-        let file = match std::fs::File::open(&self.image_path) {
-            Ok(file) => file,
-            Err(e) => {
-                self.error_message = Some(format!("Failed to open file: {}", e));
-                return;
-            }
+    /// Exports whichever tab currently has focus. There's no per-tab
+    /// addressing on the wire yet, so "the loaded animation" means
+    /// whatever the user is looking at.
+    fn export_focused_tab(&mut self, request: ExportRequest) {
+        let Some((_, tab)) = self.tree.find_active_focused() else {
+            self.toasts.push(Message::Error("No image tab is open to export".to_string()));
+            return;
         };
 
-        let decoder = GifDecoder::new(file).unwrap();
-        let frames = decoder.into_frames();
-        let mut gif_frames = Vec::new();
-
-        for frame_result in frames {
-            match frame_result {
-                Ok(frame) => {
-                    let delay = frame.delay();
-                    let duration = std::time::Duration::from(delay);
-                    let buffer = frame.into_buffer();
-                    let (width, height) = buffer.dimensions();
-
-                    let color_image = egui::ColorImage::from_rgba_unmultiplied(
-                        [width as usize, height as usize],
-                        &buffer.into_raw()
-                    );
-
-                    gif_frames.push((color_image, duration));
-                }
-                Err(e) => {
-                    self.error_message = Some(format!("Failed to decode frame: {}", e));
-                    return;
-                }
-            }
-        }
-
-        if !gif_frames.is_empty() {
-            let (width, height) = (gif_frames[0].0.width(), gif_frames[0].0.height());
-            self.original_size = Some(egui::vec2(width as f32, height as f32));
-            self.gif_frames = Some(gif_frames);
-            self.current_frame = 0;
-            self.last_frame_time = std::time::Instant::now();
-            self.is_animated = true;
-            self.update_texture(ctx);
-        }
-    }
-
-    fn update_texture(&mut self, ctx: &egui::Context) {
-        if let Some(ref frames) = self.gif_frames {
-            if self.current_frame < frames.len() {
-                let texture = ctx.load_texture(
-                    format!("gif_frame_{}", self.current_frame),
-                    frames[self.current_frame].0.clone(),
-                    egui::TextureFilter::Linear,
-                );
-                self.texture = Some(texture);
-            }
+        match tab.export_gif(&request.output_path, &request.options) {
+            Ok(()) => self.toasts.push(Message::Info(format!(
+                "Exported GIF to {}",
+                request.output_path.display()
+            ))),
+            Err(err) => self.toasts.push(Message::Error(format!("Export failed: {}", err))),
         }
     }
 }
@@ -266,6 +157,45 @@ fn get_image_dimensions(path: &str) -> Option<(u32, u32)> {
         .into_dimensions().ok()
 }
 
+/// Connects to a running Timba instance and sends it `command`, printing
+/// the reply. Unlike opening an image, these commands don't make sense to
+/// bootstrap a new instance for, so there's nothing to fall back to if no
+/// instance is listening.
+fn send_command_to_instance(command: Command) {
+    let Ok(mut stream) = UnixStream::connect(SOCKET_PATH) else {
+        eprintln!("No running Timba instance found on {}", SOCKET_PATH);
+        return;
+    };
+
+    if let Err(e) = command.write(&mut stream) {
+        eprintln!("Failed to send command: {}", e);
+        return;
+    }
+
+    match Reply::read(&mut stream) {
+        Ok(Reply::Ok(message)) => println!("{}", message),
+        Ok(Reply::Err(message)) => eprintln!("Error: {}", message),
+        Err(e) => eprintln!("Error waiting for reply: {}", e),
+    }
+}
+
+/// Parses `--export <output.gif> [max_fps] [max_dimension]` and sends the
+/// resulting `ExportGif` command.
+fn run_export_command(args: &[String]) {
+    let Some(output_path) = args.first() else {
+        eprintln!("Usage: --export <output.gif> [max_fps] [max_dimension]");
+        return;
+    };
+    let max_fps = args.get(1).and_then(|s| s.parse::<f64>().ok());
+    let max_dimension = args.get(2).and_then(|s| s.parse::<u32>().ok());
+
+    send_command_to_instance(Command::ExportGif {
+        output_path: output_path.clone(),
+        max_fps,
+        max_dimension,
+    });
+}
+
 fn main() {
     // Get command line arguments
     let args: Vec<String> = env::args().collect();
@@ -273,9 +203,19 @@ fn main() {
     // Check if an image path was provided
     if args.len() < 2 {
         eprintln!("Usage: {} <image_path>", args[0]);
+        eprintln!("       {} --export <output.gif> [max_fps] [max_dimension]", args[0]);
+        eprintln!("       {} --focus", args[0]);
+        eprintln!("       {} --ping", args[0]);
         return;
     }
 
+    match args[1].as_str() {
+        "--export" => return run_export_command(&args[2..]),
+        "--focus" => return send_command_to_instance(Command::FocusWindow),
+        "--ping" => return send_command_to_instance(Command::Ping),
+        _ => {}
+    }
+
     let image_path = args[1].clone();
 
     // Normalize and validate the path
@@ -291,27 +231,14 @@ fn main() {
         // Send the image path to the existing instance
         println!("Connected to existing Timba instance, sending path: {}", image_path);
 
-        // Send the full path to the running instance
-        if let Err(e) = stream.write_all(image_path.as_bytes()) {
+        if let Err(e) = Command::OpenImage(image_path.clone()).write(&mut stream) {
             eprintln!("Failed to send path to existing instance: {}", e);
             return;
         }
 
-        // Ensure the stream is flushed so all data is sent
-        if let Err(e) = stream.flush() {
-            eprintln!("Failed to flush stream: {}", e);
-            return;
-        }
-
-        // Wait for acknowledgment
-        let mut buffer = [0; 3];
-
-        match stream.read(&mut buffer) {
-            Ok(bytes) if bytes > 0 => {
-                let response = std::str::from_utf8(&buffer[0..bytes]).unwrap_or("???");
-                println!("Response from instance: {}", response);
-            },
-            Ok(_) => println!("No response received from instance"),
+        match Reply::read(&mut stream) {
+            Ok(Reply::Ok(message)) => println!("Response from instance: {}", message),
+            Ok(Reply::Err(message)) => eprintln!("Existing instance reported an error: {}", message),
             Err(e) => println!("Error waiting for acknowledgment: {}", e),
         }
 
@@ -323,8 +250,15 @@ fn main() {
     // Remove any stale socket file
     let _ = fs::remove_file(SOCKET_PATH);
 
-    // Create communication channel for the socket listener thread
+    // Create communication channels for the socket listener thread
     let (tx, rx) = mpsc::channel();
+    let (export_tx, export_rx) = mpsc::channel();
+    let (focus_tx, focus_rx) = mpsc::channel();
+
+    // Toasts let the socket thread report what happened without clobbering
+    // whatever image is currently displayed.
+    let toasts = ToastHub::new();
+    let toast_tx = toasts.sender();
 
     // Start listening for new connections
     thread::spawn(move || {
@@ -333,36 +267,53 @@ fn main() {
 
             for stream in listener.incoming() {
                 if let Ok(mut stream) = stream {
-                    let mut buffer = [0; 4096];  // Create a fixed-size buffer for the path
-                    match stream.read(&mut buffer) {
-                        Ok(bytes_read) if bytes_read > 0 => {
-                            // Convert the bytes to a string, ignoring any non-UTF8 characters
-                            let path = String::from_utf8_lossy(&buffer[0..bytes_read]).into_owned();
+                    match Command::read(&mut stream) {
+                        Ok(Command::OpenImage(path)) => {
                             println!("Socket received path: {}", path);
                             // Make sure we're getting a valid path
                             if Path::new(&path).exists() {
                                 println!("Path exists, sending to main thread");
                                 // Send path to main thread and acknowledge receipt
-                                if let Err(e) = tx.send(path) {
+                                if let Err(e) = tx.send(path.clone()) {
                                     eprintln!("Failed to send image path internally: {}", e);
-                                    let _ = stream.write_all(b"ERR");
+                                    let _ = toast_tx.send(Message::Error(format!("Failed to hand off image: {}", e)));
+                                    let _ = Reply::Err(format!("failed to hand off image: {}", e)).write(&mut stream);
                                 } else {
-                                    // Send acknowledgment back to client
-                                    let _ = stream.write_all(b"OK");
+                                    let _ = toast_tx.send(Message::Info(format!("Received new image over socket: {}", path)));
+                                    let _ = Reply::Ok("image received".to_string()).write(&mut stream);
                                 }
                             } else {
                                 eprintln!("Received path does not exist: {}", path);
-                                let _ = stream.write_all(b"ERR");
+                                let _ = toast_tx.send(Message::Warning(format!("Path does not exist: {}", path)));
+                                let _ = Reply::Err(format!("path does not exist: {}", path)).write(&mut stream);
+                            }
+                        }
+                        Ok(Command::ExportGif { output_path, max_fps, max_dimension }) => {
+                            let request = ExportRequest {
+                                output_path: PathBuf::from(output_path),
+                                options: ExportOptions { max_fps, max_dimension },
+                            };
+                            if export_tx.send(request).is_err() {
+                                let _ = Reply::Err("failed to hand off export request".to_string()).write(&mut stream);
+                            } else {
+                                let _ = Reply::Ok("export requested".to_string()).write(&mut stream);
+                            }
+                        }
+                        Ok(Command::FocusWindow) => {
+                            if focus_tx.send(()).is_err() {
+                                let _ = Reply::Err("failed to hand off focus request".to_string()).write(&mut stream);
+                            } else {
+                                let _ = Reply::Ok("focus requested".to_string()).write(&mut stream);
                             }
-                        },
-                        Ok(_) => {
-                            eprintln!("Received empty path over socket");
-                            let _ = stream.write_all(b"ERR");
-                        },
+                        }
+                        Ok(Command::Ping) => {
+                            let _ = Reply::Ok("pong".to_string()).write(&mut stream);
+                        }
                         Err(e) => {
                             eprintln!("Error reading from socket: {}", e);
-                            let _ = stream.write_all(b"ERR");
-                        },
+                            let _ = toast_tx.send(Message::Error(format!("Error reading from socket: {}", e)));
+                            let _ = Reply::Err(format!("failed to read command: {}", e)).write(&mut stream);
+                        }
                     }
                 }
             }
@@ -377,6 +328,7 @@ fn main() {
     ctrlc::set_handler(move || {
         println!("Cleaning up socket file...");
         let _ = fs::remove_file(&socket_path);
+        gif::cleanup_scratch_files();
         std::process::exit(0);
     }).expect("Error setting Ctrl-C handler");
 
@@ -390,7 +342,7 @@ fn main() {
         egui::vec2(800.0, 600.0)
     };
 
-    let app = TimbaApp::new(image_path, rx);
+    let app = TimbaApp::new(image_path, rx, export_rx, focus_rx, toasts);
     // Use embedded icon instead of loading from file system
     let icon_data = load_embedded_icon();
 
@@ -407,6 +359,7 @@ fn main() {
         Box::new(|_cc| Box::new(app)),
     );
 
-    // Clean up socket when exiting normally
+    // Clean up socket and GIF scratch files when exiting normally
     let _ = fs::remove_file(SOCKET_PATH);
+    gif::cleanup_scratch_files();
 }
\ No newline at end of file
@@ -0,0 +1,244 @@
+//! Length-prefixed, tagged framing for the control socket.
+//!
+//! The original protocol read into a fixed 4 KiB buffer and treated the
+//! bytes as a single bare path — silently truncating long paths and
+//! unable to carry anything but one path per connection. A frame is now
+//! `[4-byte BE length][1-byte command tag][UTF-8 payload]`, so the socket
+//! doubles as an actual control channel: `OpenImage`, `ExportGif`,
+//! `FocusWindow`, and `Ping`. Replies are similarly structured instead of
+//! a bare `OK`/`ERR` ack.
+
+use std::io::{self, Read, Write};
+
+#[derive(Debug, Clone)]
+pub enum Command {
+    OpenImage(String),
+    ExportGif {
+        output_path: String,
+        max_fps: Option<f64>,
+        max_dimension: Option<u32>,
+    },
+    FocusWindow,
+    Ping,
+}
+
+const TAG_OPEN_IMAGE: u8 = 0;
+const TAG_EXPORT_GIF: u8 = 1;
+const TAG_FOCUS_WINDOW: u8 = 2;
+const TAG_PING: u8 = 3;
+
+impl Command {
+    fn tag(&self) -> u8 {
+        match self {
+            Command::OpenImage(_) => TAG_OPEN_IMAGE,
+            Command::ExportGif { .. } => TAG_EXPORT_GIF,
+            Command::FocusWindow => TAG_FOCUS_WINDOW,
+            Command::Ping => TAG_PING,
+        }
+    }
+
+    /// Tab-separated payload; `output_path` can't contain tabs on any
+    /// platform this runs on, so this avoids pulling in a serialization
+    /// crate for three fields.
+    fn payload(&self) -> String {
+        match self {
+            Command::OpenImage(path) => path.clone(),
+            Command::ExportGif {
+                output_path,
+                max_fps,
+                max_dimension,
+            } => format!(
+                "{}\t{}\t{}",
+                output_path,
+                max_fps.map(|v| v.to_string()).unwrap_or_default(),
+                max_dimension.map(|v| v.to_string()).unwrap_or_default(),
+            ),
+            Command::FocusWindow | Command::Ping => String::new(),
+        }
+    }
+
+    fn from_tag_and_payload(tag: u8, payload: &str) -> io::Result<Self> {
+        match tag {
+            TAG_OPEN_IMAGE => Ok(Command::OpenImage(payload.to_string())),
+            TAG_EXPORT_GIF => {
+                let mut parts = payload.split('\t');
+                let output_path = parts.next().unwrap_or_default().to_string();
+                let max_fps = parts
+                    .next()
+                    .filter(|s| !s.is_empty())
+                    .and_then(|s| s.parse().ok());
+                let max_dimension = parts
+                    .next()
+                    .filter(|s| !s.is_empty())
+                    .and_then(|s| s.parse().ok());
+                Ok(Command::ExportGif {
+                    output_path,
+                    max_fps,
+                    max_dimension,
+                })
+            }
+            TAG_FOCUS_WINDOW => Ok(Command::FocusWindow),
+            TAG_PING => Ok(Command::Ping),
+            other => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown command tag {}", other),
+            )),
+        }
+    }
+
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        let payload = self.payload();
+        let payload_bytes = payload.as_bytes();
+        // +1 for the command tag byte that precedes the payload.
+        let len = (payload_bytes.len() + 1) as u32;
+        out.write_all(&len.to_be_bytes())?;
+        out.write_all(&[self.tag()])?;
+        out.write_all(payload_bytes)?;
+        out.flush()
+    }
+
+    pub fn read(input: &mut impl Read) -> io::Result<Self> {
+        let mut len_bytes = [0u8; 4];
+        input.read_exact(&mut len_bytes)?;
+        let len = u32::from_be_bytes(len_bytes) as usize;
+        if len == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "empty frame"));
+        }
+
+        let mut body = vec![0u8; len];
+        input.read_exact(&mut body)?;
+        let tag = body[0];
+        let payload = String::from_utf8_lossy(&body[1..]).into_owned();
+        Self::from_tag_and_payload(tag, &payload)
+    }
+}
+
+/// A structured reply: one tag byte (`K`/`E`) followed by a
+/// length-prefixed UTF-8 message, which may be empty.
+pub enum Reply {
+    Ok(String),
+    Err(String),
+}
+
+const REPLY_OK: u8 = b'K';
+const REPLY_ERR: u8 = b'E';
+
+impl Reply {
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        let (tag, message) = match self {
+            Reply::Ok(message) => (REPLY_OK, message.as_str()),
+            Reply::Err(message) => (REPLY_ERR, message.as_str()),
+        };
+        let message_bytes = message.as_bytes();
+        out.write_all(&[tag])?;
+        out.write_all(&(message_bytes.len() as u32).to_be_bytes())?;
+        out.write_all(message_bytes)?;
+        out.flush()
+    }
+
+    pub fn read(input: &mut impl Read) -> io::Result<Self> {
+        let mut tag = [0u8; 1];
+        input.read_exact(&mut tag)?;
+        let mut len_bytes = [0u8; 4];
+        input.read_exact(&mut len_bytes)?;
+        let len = u32::from_be_bytes(len_bytes) as usize;
+        let mut message_bytes = vec![0u8; len];
+        input.read_exact(&mut message_bytes)?;
+        let message = String::from_utf8_lossy(&message_bytes).into_owned();
+        match tag[0] {
+            REPLY_OK => Ok(Reply::Ok(message)),
+            _ => Ok(Reply::Err(message)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn roundtrip_command(command: Command) -> Command {
+        let mut buf = Vec::new();
+        command.write(&mut buf).unwrap();
+        Command::read(&mut Cursor::new(buf)).unwrap()
+    }
+
+    #[test]
+    fn open_image_roundtrips() {
+        match roundtrip_command(Command::OpenImage("/tmp/foo.png".to_string())) {
+            Command::OpenImage(path) => assert_eq!(path, "/tmp/foo.png"),
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn export_gif_roundtrips_with_and_without_optional_fields() {
+        match roundtrip_command(Command::ExportGif {
+            output_path: "/tmp/out.gif".to_string(),
+            max_fps: Some(15.0),
+            max_dimension: Some(480),
+        }) {
+            Command::ExportGif { output_path, max_fps, max_dimension } => {
+                assert_eq!(output_path, "/tmp/out.gif");
+                assert_eq!(max_fps, Some(15.0));
+                assert_eq!(max_dimension, Some(480));
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+
+        match roundtrip_command(Command::ExportGif {
+            output_path: "/tmp/out.gif".to_string(),
+            max_fps: None,
+            max_dimension: None,
+        }) {
+            Command::ExportGif { max_fps, max_dimension, .. } => {
+                assert_eq!(max_fps, None);
+                assert_eq!(max_dimension, None);
+            }
+            other => panic!("unexpected command: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn focus_window_and_ping_roundtrip() {
+        assert!(matches!(roundtrip_command(Command::FocusWindow), Command::FocusWindow));
+        assert!(matches!(roundtrip_command(Command::Ping), Command::Ping));
+    }
+
+    #[test]
+    fn read_rejects_unknown_tag() {
+        let mut buf = Vec::new();
+        let len: u32 = 1; // tag byte only
+        buf.extend_from_slice(&len.to_be_bytes());
+        buf.push(255); // not a valid command tag
+        let err = Command::read(&mut Cursor::new(buf)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn read_rejects_empty_frame() {
+        let mut buf = Vec::new();
+        let len: u32 = 0;
+        buf.extend_from_slice(&len.to_be_bytes());
+        let err = Command::read(&mut Cursor::new(buf)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "empty frame");
+    }
+
+    #[test]
+    fn reply_ok_and_err_roundtrip() {
+        let mut buf = Vec::new();
+        Reply::Ok("pong".to_string()).write(&mut buf).unwrap();
+        match Reply::read(&mut Cursor::new(buf)).unwrap() {
+            Reply::Ok(message) => assert_eq!(message, "pong"),
+            Reply::Err(_) => panic!("expected Ok"),
+        }
+
+        let mut buf = Vec::new();
+        Reply::Err("boom".to_string()).write(&mut buf).unwrap();
+        match Reply::read(&mut Cursor::new(buf)).unwrap() {
+            Reply::Err(message) => assert_eq!(message, "boom"),
+            Reply::Ok(_) => panic!("expected Err"),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Toast notification subsystem.
+//!
+//! Replaces the old `error_message` field, which blanked out the whole view
+//! on any failure. Messages are pushed onto a stack of auto-expiring
+//! overlay toasts instead, so the socket listener and the decode paths can
+//! report something ("received new image over socket", "failed to decode
+//! frame 12") without hiding the image already on screen.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+use eframe::egui;
+
+/// How long a toast stays fully visible before it starts fading.
+const VISIBLE_FOR: Duration = Duration::from_secs(3);
+/// How long the fade-out takes once `VISIBLE_FOR` has elapsed.
+const FADE_FOR: Duration = Duration::from_millis(500);
+
+#[derive(Clone)]
+pub enum Message {
+    Info(String),
+    Warning(String),
+    Error(String),
+}
+
+impl Message {
+    fn text(&self) -> &str {
+        match self {
+            Message::Info(text) | Message::Warning(text) | Message::Error(text) => text,
+        }
+    }
+
+    fn color(&self) -> egui::Color32 {
+        match self {
+            Message::Info(_) => egui::Color32::from_rgb(64, 132, 214),
+            Message::Warning(_) => egui::Color32::from_rgb(214, 160, 48),
+            Message::Error(_) => egui::Color32::from_rgb(214, 64, 64),
+        }
+    }
+}
+
+struct Toast {
+    message: Message,
+    shown_at: Instant,
+}
+
+/// Stack of active toasts plus the receiving end of a channel that other
+/// threads use to report messages without touching app state directly.
+pub struct ToastHub {
+    sender: Sender<Message>,
+    receiver: Receiver<Message>,
+    active: Vec<Toast>,
+}
+
+impl ToastHub {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sender,
+            receiver,
+            active: Vec::new(),
+        }
+    }
+
+    /// Clones the sending half so background threads (e.g. the socket
+    /// listener) can push messages in.
+    pub fn sender(&self) -> Sender<Message> {
+        self.sender.clone()
+    }
+
+    pub fn push(&mut self, message: Message) {
+        self.active.push(Toast {
+            message,
+            shown_at: Instant::now(),
+        });
+    }
+
+    /// Drains any messages queued from other threads and drops toasts that
+    /// have fully faded out. Call once per frame.
+    pub fn update(&mut self) {
+        while let Ok(message) = self.receiver.try_recv() {
+            self.push(message);
+        }
+        self.active
+            .retain(|toast| toast.shown_at.elapsed() < VISIBLE_FOR + FADE_FOR);
+    }
+
+    /// Draws the current toasts stacked in the corner, on top of whatever
+    /// else was drawn this frame.
+    pub fn show(&self, ctx: &egui::Context) {
+        if self.active.is_empty() {
+            return;
+        }
+
+        egui::Area::new("timba_toasts")
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                for toast in self.active.iter().rev() {
+                    let elapsed = toast.shown_at.elapsed();
+                    let alpha = if elapsed <= VISIBLE_FOR {
+                        1.0
+                    } else {
+                        let fade = (elapsed - VISIBLE_FOR).as_secs_f32() / FADE_FOR.as_secs_f32();
+                        (1.0 - fade).max(0.0)
+                    };
+
+                    let fill = toast.message.color().linear_multiply(alpha);
+                    let text_color = egui::Color32::WHITE.linear_multiply(alpha);
+
+                    egui::Frame::popup(ui.style()).fill(fill).show(ui, |ui| {
+                        ui.colored_label(text_color, toast.message.text());
+                    });
+                }
+            });
+    }
+}
@@ -0,0 +1,238 @@
+//! Video playback backed by `ffmpeg-next`, feeding the same
+//! `DecodedFrame`/`FramePlayer` pipeline GIFs use.
+//!
+//! Frames are decoded lazily on a worker thread — never all at once, so
+//! long videos don't exhaust memory — and timed against each frame's
+//! presentation timestamp rather than a fixed delay, since video frame
+//! durations vary.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, SyncSender};
+use std::time::Duration;
+
+use eframe::egui::ColorImage;
+use ffmpeg_next as ffmpeg;
+use ffmpeg::format::Pixel;
+use ffmpeg::media::Type;
+use ffmpeg::software::scaling;
+use ffmpeg::util::frame::video::Video as FfmpegFrame;
+
+use crate::frame::{self, DecodeMsg, DecodedFrame, FramePlayer};
+
+/// Extensions routed to the video backend instead of the image/GIF paths.
+pub const VIDEO_EXTENSIONS: &[&str] = &["mp4", "webm", "mkv", "mov", "avi"];
+
+/// Fallback frame delay used when a frame has no usable timestamp delta
+/// (roughly 30fps).
+const FALLBACK_DELAY: Duration = Duration::from_millis(33);
+
+pub struct VideoPlayer {
+    path: PathBuf,
+    receiver: Receiver<DecodeMsg>,
+    current: Option<DecodedFrame>,
+    finished: bool,
+}
+
+impl VideoPlayer {
+    pub fn spawn(path: &Path) -> Result<Self, String> {
+        let path = path.to_path_buf();
+        let receiver = start_decode_thread(&path);
+
+        let mut player = Self {
+            path,
+            receiver,
+            current: None,
+            finished: false,
+        };
+        // Block for the first frame so callers have something to show
+        // immediately; everything after this is streamed lazily.
+        player.pull_next()?;
+        Ok(player)
+    }
+
+    fn pull_next(&mut self) -> Result<(), String> {
+        match self.receiver.recv() {
+            Ok(DecodeMsg::Frame(frame)) => {
+                self.current = Some(frame);
+                Ok(())
+            }
+            Ok(DecodeMsg::Done) => {
+                self.finished = true;
+                Ok(())
+            }
+            Ok(DecodeMsg::Error(err)) => Err(err),
+            Err(_) => {
+                self.finished = true;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FramePlayer for VideoPlayer {
+    fn current_frame(&self) -> Option<&DecodedFrame> {
+        self.current.as_ref()
+    }
+
+    fn advance(&mut self) -> std::io::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        self.pull_next()
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
+    }
+
+    /// Seeking restarts decoding from the beginning of the file; ffmpeg's
+    /// own seek is a future optimization, this is the simple correct path.
+    fn seek_to_start(&mut self) -> std::io::Result<()> {
+        self.receiver = start_decode_thread(&self.path);
+        self.current = None;
+        self.finished = false;
+        self.pull_next()
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
+    }
+
+    /// Decodes the whole file on a fresh thread and collects every frame,
+    /// leaving the player's own playback position untouched. There's no
+    /// scratch-file cache for video like `GifPlayer` has, so this assumes
+    /// the clip is short enough to hold entirely in memory — reasonable
+    /// for the "turn a clip into a shareable GIF" use case this serves.
+    fn export_frames(&mut self) -> Result<Vec<DecodedFrame>, String> {
+        let receiver = start_decode_thread(&self.path);
+        let mut frames = Vec::new();
+        loop {
+            match receiver.recv() {
+                Ok(DecodeMsg::Frame(frame)) => frames.push(frame),
+                Ok(DecodeMsg::Done) => break,
+                Ok(DecodeMsg::Error(err)) => return Err(err),
+                Err(_) => break,
+            }
+        }
+        Ok(frames)
+    }
+}
+
+fn start_decode_thread(path: &Path) -> Receiver<DecodeMsg> {
+    let path = path.to_path_buf();
+    frame::spawn_decode_thread(move |tx| decode_loop(&path, tx))
+}
+
+fn decode_loop(path: &Path, tx: SyncSender<DecodeMsg>) {
+    if let Err(err) = ffmpeg::init() {
+        let _ = tx.send(DecodeMsg::Error(format!("failed to init ffmpeg: {}", err)));
+        return;
+    }
+
+    let mut ictx = match ffmpeg::format::input(&path) {
+        Ok(ictx) => ictx,
+        Err(err) => {
+            let _ = tx.send(DecodeMsg::Error(format!("failed to open video: {}", err)));
+            return;
+        }
+    };
+
+    let stream = match ictx.streams().best(Type::Video) {
+        Some(stream) => stream,
+        None => {
+            let _ = tx.send(DecodeMsg::Error("no video stream found".to_string()));
+            return;
+        }
+    };
+    let stream_index = stream.index();
+    let time_base = stream.time_base();
+
+    let context = match ffmpeg::codec::context::Context::from_parameters(stream.parameters()) {
+        Ok(context) => context,
+        Err(err) => {
+            let _ = tx.send(DecodeMsg::Error(format!("failed to read codec parameters: {}", err)));
+            return;
+        }
+    };
+    let mut decoder = match context.decoder().video() {
+        Ok(decoder) => decoder,
+        Err(err) => {
+            let _ = tx.send(DecodeMsg::Error(format!("failed to open video decoder: {}", err)));
+            return;
+        }
+    };
+
+    let mut scaler = match scaling::context::Context::get(
+        decoder.format(),
+        decoder.width(),
+        decoder.height(),
+        Pixel::RGBA,
+        decoder.width(),
+        decoder.height(),
+        scaling::flag::Flags::BILINEAR,
+    ) {
+        Ok(scaler) => scaler,
+        Err(err) => {
+            let _ = tx.send(DecodeMsg::Error(format!("failed to create scaler: {}", err)));
+            return;
+        }
+    };
+
+    let mut decoded = FfmpegFrame::empty();
+    let mut rgba = FfmpegFrame::empty();
+    // A frame's own on-screen duration is the gap to the *next* frame's
+    // PTS, not the previous one, so the most recently decoded frame is
+    // held back until its successor arrives and its real delay is known.
+    let mut pending: Option<(ColorImage, i64)> = None;
+
+    for (packet_stream, packet) in ictx.packets() {
+        if packet_stream.index() != stream_index {
+            continue;
+        }
+        if decoder.send_packet(&packet).is_err() {
+            continue;
+        }
+
+        while decoder.receive_frame(&mut decoded).is_ok() {
+            if scaler.run(&decoded, &mut rgba).is_err() {
+                continue;
+            }
+
+            let pts = decoded.pts().unwrap_or(0);
+            let width = rgba.width() as usize;
+            let height = rgba.height() as usize;
+            // ffmpeg pads each row to its own stride (often 32-byte
+            // aligned), which usually isn't `width * 4` — reading the
+            // plane directly into `ColorImage` either panics its
+            // `width*height*4 == data.len()` assert or renders skewed, so
+            // each row is copied out without the padding first.
+            let stride = rgba.stride(0);
+            let data = rgba.data(0);
+            let mut packed = Vec::with_capacity(width * height * 4);
+            for row in 0..height {
+                let start = row * stride;
+                packed.extend_from_slice(&data[start..start + width * 4]);
+            }
+            let image = ColorImage::from_rgba_unmultiplied([width, height], &packed);
+
+            if let Some((pending_image, pending_pts)) = pending.take() {
+                let delay = pts_delta_to_duration(pts - pending_pts, time_base);
+                if tx.send(DecodeMsg::Frame(DecodedFrame { image: pending_image, delay })).is_err() {
+                    // UI side dropped the player; nothing left to do.
+                    return;
+                }
+            }
+            pending = Some((image, pts));
+        }
+    }
+
+    // The last frame has no successor to time itself against; fall back
+    // to the nominal delay rather than dropping it.
+    if let Some((image, _)) = pending {
+        let _ = tx.send(DecodeMsg::Frame(DecodedFrame { image, delay: FALLBACK_DELAY }));
+    }
+
+    let _ = tx.send(DecodeMsg::Done);
+}
+
+fn pts_delta_to_duration(delta: i64, time_base: ffmpeg::Rational) -> Duration {
+    if delta <= 0 || time_base.denominator() == 0 {
+        return FALLBACK_DELAY;
+    }
+    let seconds = delta as f64 * time_base.numerator() as f64 / time_base.denominator() as f64;
+    Duration::try_from_secs_f64(seconds).unwrap_or(FALLBACK_DELAY)
+}
@@ -0,0 +1,243 @@
+//! Per-tab image/animation state.
+//!
+//! Before docking support, the app held a single image's state directly
+//! on `TimbaApp`. Each socket-delivered image now gets its own `ImageView`
+//! living inside an `egui_dock` tab instead of clobbering one set of
+//! fields, so several images can stay open side by side.
+
+use std::path::Path;
+use std::time::Instant;
+
+use eframe::egui;
+
+use crate::export::{self, ExportOptions};
+use crate::frame::FramePlayer;
+use crate::gif::GifPlayer;
+use crate::video::{self, VideoPlayer};
+
+pub struct ImageView {
+    pub path: String,
+    texture: Option<egui::TextureHandle>,
+    original_size: Option<egui::Vec2>,
+    animation: Option<Box<dyn FramePlayer>>,
+    last_frame_time: Instant,
+    is_animated: bool,
+    /// Play/pause toggle for animated sources (mainly useful for video).
+    paused: bool,
+    /// Set once `load` fails, so a persistently broken source (missing
+    /// file, no ffmpeg, corrupt GIF) is reported once instead of retried
+    /// and re-toasted on every frame.
+    load_error: Option<String>,
+}
+
+impl ImageView {
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            texture: None,
+            original_size: None,
+            animation: None,
+            last_frame_time: Instant::now(),
+            is_animated: false,
+            paused: false,
+            load_error: None,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        Path::new(&self.path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.path.clone())
+    }
+
+    /// Dispatches to the static/GIF/video loader based on file extension.
+    pub fn load(&mut self, ctx: &egui::Context) -> Result<(), String> {
+        let path = Path::new(&self.path);
+        let extension = path
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+
+        if extension == "gif" {
+            self.load_gif(ctx)
+        } else if video::VIDEO_EXTENSIONS.contains(&extension.as_str()) {
+            self.load_video(ctx)
+        } else {
+            self.load_static_image(ctx)
+        }
+    }
+
+    fn load_static_image(&mut self, ctx: &egui::Context) -> Result<(), String> {
+        let path = Path::new(&self.path);
+        let img = image::open(path).map_err(|err| format!("Failed to load image: {}", err))?;
+
+        let width = img.width() as f32;
+        let height = img.height() as f32;
+        let size = [img.width() as _, img.height() as _];
+        let pixels = img.to_rgba8().into_vec();
+
+        self.original_size = Some(egui::vec2(width, height));
+
+        let texture = ctx.load_texture(
+            path.file_name().unwrap().to_string_lossy(),
+            egui::ColorImage::from_rgba_unmultiplied(size, &pixels),
+            egui::TextureFilter::Linear,
+        );
+
+        self.texture = Some(texture);
+        self.is_animated = false;
+        self.animation = None;
+        Ok(())
+    }
+
+    fn load_gif(&mut self, ctx: &egui::Context) -> Result<(), String> {
+        // Decoding happens on a background thread so multi-hundred-frame
+        // GIFs don't freeze the window; see `gif::GifPlayer`.
+        let player = GifPlayer::spawn(Path::new(&self.path))
+            .map_err(|err| format!("Failed to open GIF: {}", err))?;
+        self.start_animation(ctx, Box::new(player));
+        Ok(())
+    }
+
+    fn load_video(&mut self, ctx: &egui::Context) -> Result<(), String> {
+        // Decoded lazily on a worker thread, just like GIFs; see
+        // `video::VideoPlayer`.
+        let player =
+            VideoPlayer::spawn(Path::new(&self.path)).map_err(|err| format!("Failed to open video: {}", err))?;
+        self.start_animation(ctx, Box::new(player));
+        Ok(())
+    }
+
+    fn start_animation(&mut self, ctx: &egui::Context, player: Box<dyn FramePlayer>) {
+        if let Some(frame) = player.current_frame() {
+            let (width, height) = (frame.image.width(), frame.image.height());
+            self.original_size = Some(egui::vec2(width as f32, height as f32));
+        }
+
+        self.animation = Some(player);
+        self.last_frame_time = Instant::now();
+        self.is_animated = true;
+        self.paused = false;
+        self.update_texture(ctx);
+    }
+
+    fn update_texture(&mut self, ctx: &egui::Context) {
+        if let Some(ref player) = self.animation {
+            if let Some(frame) = player.current_frame() {
+                let texture = ctx.load_texture(
+                    "animation_frame",
+                    frame.image.clone(),
+                    egui::TextureFilter::Linear,
+                );
+                self.texture = Some(texture);
+            }
+        }
+    }
+
+    /// Space toggles play/pause, Home seeks back to the first frame.
+    /// Seeking is mainly meaningful for video; GIFs just replay.
+    fn handle_playback_keys(&mut self, ctx: &egui::Context) -> Result<(), String> {
+        let input = ctx.input();
+        let toggle_pause = input.key_pressed(egui::Key::Space);
+        let seek_start = input.key_pressed(egui::Key::Home);
+        drop(input);
+
+        if toggle_pause {
+            self.paused = !self.paused;
+        }
+        if seek_start {
+            if let Some(ref mut player) = self.animation {
+                player
+                    .seek_to_start()
+                    .map_err(|err| format!("Failed to seek to start: {}", err))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Advances animation timing and (re)loads the image if needed. Called
+    /// once per frame for the visible tab. A load failure is recorded on
+    /// `load_error` and reported only once, not retried every frame.
+    pub fn tick(&mut self, ctx: &egui::Context) -> Result<(), String> {
+        if self.texture.is_none() && self.load_error.is_none() {
+            if let Err(err) = self.load(ctx) {
+                self.load_error = Some(err.clone());
+                return Err(err);
+            }
+        }
+
+        if self.load_error.is_some() {
+            return Ok(());
+        }
+
+        if !self.is_animated {
+            return Ok(());
+        }
+
+        self.handle_playback_keys(ctx)?;
+
+        if self.paused {
+            return Ok(());
+        }
+
+        let Some(frame_duration) = self.animation.as_ref().and_then(|p| p.current_frame()).map(|f| f.delay) else {
+            return Ok(());
+        };
+
+        let now = Instant::now();
+        if now.duration_since(self.last_frame_time) >= frame_duration {
+            self.last_frame_time = now;
+            self.animation
+                .as_mut()
+                .unwrap()
+                .advance()
+                .map_err(|err| format!("Failed to advance frame: {}", err))?;
+            self.update_texture(ctx);
+        }
+        Ok(())
+    }
+
+    /// Exports the currently loaded animation as a quantized GIF. Static
+    /// images have no `FramePlayer` to pull frames from and are rejected.
+    pub fn export_gif(&mut self, output_path: &Path, options: &ExportOptions) -> Result<(), String> {
+        let animation = self
+            .animation
+            .as_mut()
+            .ok_or_else(|| "this tab has no animation loaded to export".to_string())?;
+        let frames = animation.export_frames()?;
+        export::export_gif(&frames, output_path, options)
+    }
+
+    /// Draws the image scaled to fit and centered in the available space.
+    pub fn show(&self, ui: &mut egui::Ui) {
+        if let Some(err) = &self.load_error {
+            ui.label(format!("Failed to load: {}", err));
+            return;
+        }
+
+        let (Some(texture), Some(original_size)) = (&self.texture, self.original_size) else {
+            ui.label("Loading image...");
+            return;
+        };
+
+        let available_size = ui.available_size();
+
+        let scale_x = available_size.x / original_size.x;
+        let scale_y = available_size.y / original_size.y;
+        let scale = scale_x.min(scale_y).min(1.0); // Don't scale above 100%
+
+        let displayed_size = egui::vec2(original_size.x * scale, original_size.y * scale);
+
+        let padding_x = (available_size.x - displayed_size.x) / 2.0;
+        let padding_y = (available_size.y - displayed_size.y) / 2.0;
+
+        ui.allocate_space(egui::vec2(available_size.x, padding_y));
+
+        ui.horizontal(|ui| {
+            ui.add_space(padding_x);
+            ui.add(egui::Image::new(texture, displayed_size));
+        });
+    }
+}